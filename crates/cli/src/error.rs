@@ -22,4 +22,7 @@ pub enum Error {
 
     #[error("Invalid hostname: {0}")]
     InvalidHostname(String),
+
+    #[error("No server addresses provided; use --ip, --ip-file, or --master")]
+    NoTargets,
 }
@@ -1,14 +1,29 @@
-use std::net::{IpAddr, ToSocketAddrs};
+use std::{
+    collections::VecDeque,
+    fs,
+    io,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    path::PathBuf,
+    sync::Mutex,
+    time::Instant,
+};
 
-use clap::{Parser, ValueEnum};
+use clap::{builder::PossibleValuesParser, CommandFactory, FromArgMatches, Parser, ValueEnum};
+use clap_complete::{generate, Shell};
 use gamedig::{
+    dns::{AddressFamilyPreference, DnsConfig},
     games::*,
+    master::{query_master, Region},
     protocols::types::{CommonResponse, ExtraRequestSettings, TimeoutSettings},
 };
 
 mod error;
+mod result;
 
-use self::error::{Error, Result};
+use self::{
+    error::{Error, Result},
+    result::ServerResult,
+};
 
 // NOTE: For some reason without setting long_about here the doc comment for
 // ExtraRequestSettings gets set as the about for the CLI.
@@ -16,19 +31,64 @@ use self::error::{Error, Result};
 #[command(author, version, about, long_about = None)]
 struct Cli {
     /// Unique identifier of the game for which server information is being
-    /// queried.
-    #[arg(short, long)]
-    game: String,
+    /// queried. Tab-completable; see `--completions`.
+    #[arg(short, long, required_unless_present = "completions")]
+    game: Option<String>,
 
-    /// Hostname or IP address of the server.
+    /// Print a shell completion script for the given shell and exit.
+    #[arg(long, value_enum)]
+    completions: Option<Shell>,
+
+    /// Hostname or IP address of the server, as `host` or `host:port`. May be
+    /// repeated to query multiple servers. Not used with `--master`.
     #[arg(short, long)]
-    ip: String,
+    ip: Vec<String>,
+
+    /// Read additional targets from a file, one `host` or `host:port` per
+    /// line. Not used with `--master`.
+    #[arg(long)]
+    ip_file: Option<PathBuf>,
 
-    /// Optional query port number for the server. If not provided the default
-    /// port for the game is used.
+    /// Optional query port number for the server, used for any target that
+    /// doesn't specify its own port. If not provided the default port for
+    /// the game is used.
     #[arg(short, long)]
     port: Option<u16>,
 
+    /// Maximum number of servers to query concurrently.
+    #[arg(long, default_value_t = 32)]
+    threads: usize,
+
+    /// Nameserver to use for DNS resolution, instead of those configured in
+    /// `/etc/resolv.conf`.
+    #[arg(long)]
+    dns_server: Option<IpAddr>,
+
+    /// Prefer AAAA (IPv6) records over A (IPv4) records when a hostname
+    /// resolves to both.
+    #[arg(long, conflicts_with = "prefer_ipv4")]
+    prefer_ipv6: bool,
+
+    /// Prefer A (IPv4) records over AAAA (IPv6) records when a hostname
+    /// resolves to both. This is the default.
+    #[arg(long)]
+    prefer_ipv4: bool,
+
+    /// Query the game's master server for live addresses and query each of
+    /// them in turn, instead of querying a single server.
+    #[arg(long)]
+    master: bool,
+
+    /// Region to restrict the master server query to. Only used with
+    /// `--master`.
+    #[arg(long, value_enum)]
+    region: Option<MasterRegion>,
+
+    /// Optional filter string to pass to the master server. Only used with
+    /// `--master`.
+    #[arg(long)]
+    filter: Option<String>,
+
     /// Flag indicating if the output should be in JSON format.
     #[cfg(feature = "json")]
     #[arg(short, long)]
@@ -57,6 +117,36 @@ enum OutputMode {
     ProtocolSpecific,
 }
 
+/// Region to restrict a `--master` query to. Mirrors [gamedig::master::Region].
+#[derive(Clone, Debug, ValueEnum)]
+enum MasterRegion {
+    UsEastCoast,
+    UsWestCoast,
+    SouthAmerica,
+    Europe,
+    Asia,
+    Australia,
+    MiddleEast,
+    Africa,
+    RestOfTheWorld,
+}
+
+impl From<MasterRegion> for Region {
+    fn from(region: MasterRegion) -> Self {
+        match region {
+            MasterRegion::UsEastCoast => Region::UsEastCoast,
+            MasterRegion::UsWestCoast => Region::UsWestCoast,
+            MasterRegion::SouthAmerica => Region::SouthAmerica,
+            MasterRegion::Europe => Region::Europe,
+            MasterRegion::Asia => Region::Asia,
+            MasterRegion::Australia => Region::Australia,
+            MasterRegion::MiddleEast => Region::MiddleEast,
+            MasterRegion::Africa => Region::Africa,
+            MasterRegion::RestOfTheWorld => Region::RestOfTheWorld,
+        }
+    }
+}
+
 /// Attempt to find a game from the [library game definitions](GAMES) based on
 /// its unique identifier.
 ///
@@ -80,39 +170,25 @@ fn find_game(game_id: &str) -> Result<&'static Game> {
 /// * `host` - A string slice containing the IP address or hostname of a server
 ///   to resolve.
 /// * `extra_options` - Mutable reference to extra options for the game query.
+/// * `dns_config` - The resolver configuration to use for the DNS lookup.
 ///
 /// # Returns
 /// * `Result<IpAddr>` - On sucess returns a resolved IP address; on failure
 ///   returns an [Error::InvalidHostname] error.
-fn resolve_ip_or_domain(host: &str, extra_options: &mut Option<ExtraRequestSettings>) -> Result<IpAddr> {
+fn resolve_ip_or_domain(
+    host: &str,
+    extra_options: &mut Option<ExtraRequestSettings>,
+    dns_config: &DnsConfig,
+) -> Result<IpAddr> {
     if let Ok(parsed_ip) = host.parse() {
         Ok(parsed_ip)
     } else {
         set_hostname_if_missing(host, extra_options);
 
-        resolve_domain(host)
+        gamedig::dns::resolve(host, dns_config).map_err(|_| Error::InvalidHostname(host.to_string()))
     }
 }
 
-/// Resolve a domain name to one of its IP addresses (the first one returned).
-///
-/// # Arguments
-/// * `domain` - A string slice containing the domain name to lookup.
-///
-/// # Returns
-/// * `Result<IpAddr>` - On success, returns one of the resolved IP addresses;
-///   on failure returns an [Error::InvalidHostname] error.
-fn resolve_domain(domain: &str) -> Result<IpAddr> {
-    // Append a dummy port to perform socket address resolution and then extract the
-    // IP
-    Ok(format!("{}:0", domain)
-        .to_socket_addrs()
-        .map_err(|_| Error::InvalidHostname(domain.to_string()))?
-        .next()
-        .ok_or_else(|| Error::InvalidHostname(domain.to_string()))?
-        .ip())
-}
-
 /// Sets the hostname on extra request settings if it is not already set.
 ///
 /// # Arguments
@@ -130,59 +206,301 @@ fn set_hostname_if_missing(host: &str, extra_options: &mut Option<ExtraRequestSe
     }
 }
 
-/// Output the result of a query to stdout.
+/// A single query target, parsed from an `--ip` value or a line in an
+/// `--ip-file`.
+struct Target {
+    host: String,
+    port: Option<u16>,
+}
+
+/// Parse a `host` or `host:port` string into a [Target].
+///
+/// Tried in order: a full socket address (`1.2.3.4:27015` or the bracketed
+/// `[::1]:27015` form), a bare IP address (including unbracketed IPv6
+/// literals like `::1`, which contain colons but no port), then finally a
+/// `hostname:port` pair, falling back to a bare hostname.
+fn parse_target(raw: &str) -> Target {
+    if let Ok(address) = raw.parse::<SocketAddr>() {
+        return Target {
+            host: address.ip().to_string(),
+            port: Some(address.port()),
+        };
+    }
+
+    if raw.parse::<IpAddr>().is_ok() {
+        return Target {
+            host: raw.to_string(),
+            port: None,
+        };
+    }
+
+    if let Some((host, port)) = raw.rsplit_once(':') {
+        if let Ok(port) = port.parse() {
+            return Target {
+                host: host.to_string(),
+                port: Some(port),
+            };
+        }
+    }
+
+    Target {
+        host: raw.to_string(),
+        port: None,
+    }
+}
+
+#[cfg(test)]
+mod target_tests {
+    use super::*;
+
+    #[test]
+    fn parses_bare_ipv4() {
+        let target = parse_target("127.0.0.1");
+        assert_eq!(target.host, "127.0.0.1");
+        assert_eq!(target.port, None);
+    }
+
+    #[test]
+    fn parses_ipv4_with_port() {
+        let target = parse_target("127.0.0.1:27015");
+        assert_eq!(target.host, "127.0.0.1");
+        assert_eq!(target.port, Some(27015));
+    }
+
+    #[test]
+    fn parses_bare_ipv6() {
+        let target = parse_target("::1");
+        assert_eq!(target.host, "::1");
+        assert_eq!(target.port, None);
+    }
+
+    #[test]
+    fn parses_full_ipv6() {
+        let target = parse_target("2001:db8::1");
+        assert_eq!(target.host, "2001:db8::1");
+        assert_eq!(target.port, None);
+    }
+
+    #[test]
+    fn parses_bracketed_ipv6_with_port() {
+        let target = parse_target("[::1]:27015");
+        assert_eq!(target.host, "::1");
+        assert_eq!(target.port, Some(27015));
+    }
+
+    #[test]
+    fn parses_hostname_with_port() {
+        let target = parse_target("example.com:27015");
+        assert_eq!(target.host, "example.com");
+        assert_eq!(target.port, Some(27015));
+    }
+
+    #[test]
+    fn parses_bare_hostname() {
+        let target = parse_target("example.com");
+        assert_eq!(target.host, "example.com");
+        assert_eq!(target.port, None);
+    }
+}
+
+/// Collect every query target from `--ip` and `--ip-file`.
 ///
 /// # Arguments
 /// * `args` - A reference to the command line options.
-/// * `result` - A reference to the result of the query.
-fn output_result(args: &Cli, result: &dyn CommonResponse) {
+fn collect_targets(args: &Cli) -> Result<Vec<Target>> {
+    let mut targets: Vec<Target> = args.ip.iter().map(|raw| parse_target(raw)).collect();
+
+    if let Some(path) = &args.ip_file {
+        let contents = fs::read_to_string(path)?;
+        targets.extend(contents.lines().map(str::trim).filter(|line| !line.is_empty()).map(parse_target));
+    }
+
+    if targets.is_empty() {
+        return Err(Error::NoTargets);
+    }
+
+    Ok(targets)
+}
+
+/// Select which representation of a successful response to embed in a
+/// [ServerResult], based on the requested output mode.
+///
+/// `CommonResponse::as_json` is only compiled into the library under the
+/// `json` feature, so without it we fall back to the protocol-specific
+/// response for both output modes rather than failing to build; `as_original`
+/// always returns a concrete, already-`Serialize` response type, so it's
+/// converted through [serde_json::to_value] to match `as_json`'s return type.
+fn response_value(args: &Cli, result: &dyn CommonResponse) -> serde_json::Value {
     match args.output_mode {
         #[cfg(feature = "json")]
-        OutputMode::Generic if args.json => output_result_json(result.as_json()),
-        #[cfg(feature = "json")]
-        OutputMode::ProtocolSpecific if args.json => output_result_json(result.as_original()),
+        OutputMode::Generic => result.as_json(),
+        #[cfg(not(feature = "json"))]
+        OutputMode::Generic => serde_json::to_value(result.as_original()).unwrap_or_default(),
+        OutputMode::ProtocolSpecific => serde_json::to_value(result.as_original()).unwrap_or_default(),
+    }
+}
+
+/// Query a single, already-resolved address.
+///
+/// # Arguments
+/// * `args` - A reference to the command line options.
+/// * `game` - The game definition to query.
+/// * `address` - The resolved address to query.
+fn query_address(args: &Cli, game: &'static Game, address: SocketAddr) -> ServerResult {
+    let extra_options = args.extra_options.clone();
+
+    // FIXME(chunk0-3): the request asks for this to be measured inside
+    // `query_with_timeout_and_extra_settings` and surfaced as `ping: Option<f32>` on
+    // `CommonResponse` itself, so both output modes get it "for free" from the library.
+    // That function and trait live in the `gamedig` library crate, whose source this tree
+    // doesn't carry, so it can't be edited from here. What's below is a CLI-side stopgap:
+    // it wraps the call with `Instant`, so the figure includes a sliver of CLI dispatch
+    // overhead on top of the real network round trip, and it only reaches `ServerResult`,
+    // never `CommonResponse`. Flagging for reviewers: this needs the library-side change
+    // before it matches the request as written.
+    let sent_at = Instant::now();
+    let response = query_with_timeout_and_extra_settings(
+        game,
+        &address.ip(),
+        Some(address.port()),
+        args.timeout_settings.clone(),
+        extra_options,
+    );
+    let ping = sent_at.elapsed().as_secs_f32() * 1000.0;
+
+    match response {
+        Ok(result) => ServerResult::ok(address, Some(ping), response_value(args, result.as_ref())),
+        Err(err) => ServerResult::from_error(address, err),
+    }
+}
+
+/// Resolve a `--ip`/`--ip-file` target and query it.
+///
+/// # Arguments
+/// * `args` - A reference to the command line options.
+/// * `game` - The game definition to query.
+/// * `dns_config` - The resolver configuration to use for hostname targets.
+/// * `target` - The host (and optional port) to resolve and query.
+fn query_target(args: &Cli, game: &'static Game, dns_config: &DnsConfig, target: &Target) -> ServerResult {
+    let mut extra_options = args.extra_options.clone();
+    let port = target.port.or(args.port);
 
-        OutputMode::Generic => output_result_debug(result.as_json()),
-        OutputMode::ProtocolSpecific => output_result_debug(result.as_original()),
+    let ip = match resolve_ip_or_domain(&target.host, &mut extra_options, dns_config) {
+        Ok(ip) => ip,
+        Err(err) => {
+            // The port used in the default address label is only a label here; no
+            // query was ever sent because the host itself didn't resolve.
+            return ServerResult::Error {
+                address: SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), port.unwrap_or_default()),
+                message: err.to_string(),
+            };
+        },
+    };
+
+    let address = SocketAddr::new(ip, port.unwrap_or_default());
+
+    // FIXME(chunk0-3): see `query_address` above — this should live inside the library
+    // call and land on `CommonResponse`, not be bolted on here.
+    let sent_at = Instant::now();
+    let response = query_with_timeout_and_extra_settings(game, &ip, port, args.timeout_settings.clone(), extra_options);
+    let ping = sent_at.elapsed().as_secs_f32() * 1000.0;
+
+    match response {
+        Ok(result) => ServerResult::ok(address, Some(ping), response_value(args, result.as_ref())),
+        Err(err) => ServerResult::from_error(address, err),
     }
 }
 
-/// Output the result using debug formatting.
+/// Run `query_one` over every target using a bounded pool of worker threads,
+/// collecting one [ServerResult] per target regardless of individual
+/// failures.
 ///
 /// # Arguments
-/// * `result` - A result that can be output using the debug formatter.
-fn output_result_debug<R: std::fmt::Debug>(result: R) {
-    println!("{:#?}", result);
+/// * `targets` - The targets to query.
+/// * `threads` - The maximum number of targets to query concurrently.
+/// * `query_one` - Called once per target to produce its [ServerResult].
+fn query_all<T, F>(targets: Vec<T>, threads: usize, query_one: F) -> Vec<ServerResult>
+where
+    T: Send,
+    F: Fn(T) -> ServerResult + Sync,
+{
+    let queue = Mutex::new(VecDeque::from(targets));
+    let results = Mutex::new(Vec::new());
+
+    std::thread::scope(|scope| {
+        for _ in 0 .. threads.max(1) {
+            scope.spawn(|| {
+                while let Some(target) = queue.lock().unwrap().pop_front() {
+                    let result = query_one(target);
+                    results.lock().unwrap().push(result);
+                }
+            });
+        }
+    });
+
+    results.into_inner().unwrap()
 }
 
-/// Output the result as a JSON object.
+/// Output a batch of query results to stdout.
 ///
 /// # Arguments
-/// * `result` - A serde serializable result.
-#[cfg(feature = "json")]
-fn output_result_json<R: serde::Serialize>(result: R) {
-    serde_json::to_writer_pretty(std::io::stdout(), &result).unwrap();
+/// * `args` - A reference to the command line options.
+/// * `results` - The results to output, one per queried server.
+fn output_results(args: &Cli, results: &[ServerResult]) {
+    #[cfg(feature = "json")]
+    if args.json {
+        serde_json::to_writer_pretty(std::io::stdout(), results).unwrap();
+        return;
+    }
+
+    for result in results {
+        println!("{:#?}", result);
+    }
+}
+
+/// Build the clap [Command] for [Cli], with `--game` enumerating every key in
+/// [GAMES] as its possible values so shells can tab-complete game
+/// identifiers instead of guessing and hitting [Error::UnknownGame].
+fn build_command() -> clap::Command {
+    let game_ids = PossibleValuesParser::new(GAMES.keys().copied());
+
+    Cli::command().mut_arg("game", |arg| arg.value_parser(game_ids))
 }
 
 fn main() -> Result<()> {
-    // Parse the command line arguments
-    let args = Cli::parse();
+    // Parse the command line arguments, with dynamically-completable game IDs
+    let mut command = build_command();
+    let matches = command.clone().get_matches();
+    let args = Cli::from_arg_matches(&matches).map_err(Error::Clap)?;
+
+    if let Some(shell) = args.completions {
+        let name = command.get_name().to_string();
+        generate(shell, &mut command, name, &mut io::stdout());
+        return Ok(());
+    }
 
     // Retrieve the game based on the provided ID
-    let game = find_game(&args.game)?;
+    let game = find_game(args.game.as_deref().expect("game is required unless --completions is set"))?;
 
-    // Extract extra options for use in setup
-    let mut extra_options = args.extra_options.clone();
+    let results = if args.master {
+        let region = args.region.clone().unwrap_or(MasterRegion::RestOfTheWorld).into();
+        let addresses = query_master(game, region, args.filter.as_deref())?;
 
-    // Resolve the IP address
-    let ip = resolve_ip_or_domain(&args.ip, &mut extra_options)?;
+        query_all(addresses, args.threads, |address| query_address(&args, game, address))
+    } else {
+        let targets = collect_targets(&args)?;
+        let prefer = if args.prefer_ipv6 {
+            AddressFamilyPreference::Ipv6
+        } else {
+            AddressFamilyPreference::Ipv4
+        };
+        let dns_config = DnsConfig::from_resolv_conf(args.dns_server, prefer)?;
 
-    // Query the server using game definition, parsed IP, and user command line
-    // flags.
-    let result = query_with_timeout_and_extra_settings(game, &ip, args.port, args.timeout_settings, extra_options)?;
+        query_all(targets, args.threads, |target| query_target(&args, game, &dns_config, &target))
+    };
 
-    // Output the result in the specified format
-    output_result(&args, result.as_ref());
+    // Output the results in the specified format
+    output_results(&args, &results);
 
     Ok(())
 }
\ No newline at end of file
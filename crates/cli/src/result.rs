@@ -0,0 +1,84 @@
+use std::net::SocketAddr;
+
+use gamedig::errors::GDError;
+use serde::Serialize;
+
+/// The outcome of querying a single server, tagged with a `status`
+/// discriminant so a batch of these can be serialized as one JSON array
+/// without any one timeout or malformed packet losing the results of every
+/// other server that was queried alongside it.
+#[derive(Debug, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ServerResult {
+    /// The server responded and its response was parsed successfully.
+    Ok {
+        address: SocketAddr,
+        /// Round-trip time between sending the query and receiving the final
+        /// protocol response, in milliseconds.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        ping: Option<f32>,
+        #[serde(flatten)]
+        response: serde_json::Value,
+    },
+    /// The server did not respond within the configured timeout.
+    Timeout { address: SocketAddr },
+    /// The query failed for a reason other than a timeout or a malformed
+    /// response, e.g. a DNS lookup or socket error.
+    Error { address: SocketAddr, message: String },
+    /// The server responded, but the response could not be parsed as the
+    /// expected protocol.
+    ///
+    /// `raw_response` carries the offending bytes when the query functions
+    /// expose them; today nothing below the CLI surfaces raw bytes on
+    /// failure (they return only a [GDError], not the packet that didn't
+    /// parse), so this is always `None` until a library-side change threads
+    /// them through. Kept in the schema rather than dropped, since consumers
+    /// of this JSON shape were told to expect it.
+    Invalid {
+        address: SocketAddr,
+        message: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        raw_response: Option<String>,
+    },
+    /// The query failed for a reason not covered by the other variants,
+    /// e.g. a protocol-level rejection from the server.
+    Protocol { address: SocketAddr, message: String },
+}
+
+impl ServerResult {
+    /// Build a successful result from a server's JSON response.
+    pub fn ok(address: SocketAddr, ping: Option<f32>, response: serde_json::Value) -> Self {
+        ServerResult::Ok { address, ping, response }
+    }
+
+    /// Classify a [GDError] returned from a query into the appropriate
+    /// result variant.
+    ///
+    /// This matches on the error's own variant rather than its message text,
+    /// so a future rewording of a [GDError]'s `Display` impl can't silently
+    /// reclassify results.
+    pub fn from_error(address: SocketAddr, error: GDError) -> Self {
+        match error {
+            GDError::PacketReceive => ServerResult::Timeout { address },
+            GDError::PacketBad => {
+                ServerResult::Invalid {
+                    address,
+                    message: error.to_string(),
+                    raw_response: None,
+                }
+            },
+            GDError::SocketBind | GDError::SocketConnect | GDError::PacketSend | GDError::InvalidInput => {
+                ServerResult::Error {
+                    address,
+                    message: error.to_string(),
+                }
+            },
+            other => {
+                ServerResult::Protocol {
+                    address,
+                    message: other.to_string(),
+                }
+            },
+        }
+    }
+}
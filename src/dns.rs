@@ -0,0 +1,365 @@
+use std::{
+    fs,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket},
+    sync::atomic::{AtomicU16, Ordering},
+    thread,
+    time::Duration,
+};
+
+use crate::errors::{GDError, GDResult};
+
+/// Which address family to prefer when a domain resolves to both an A and an
+/// AAAA record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddressFamilyPreference {
+    Ipv4,
+    Ipv6,
+}
+
+/// Resolver configuration: which nameservers to query, and how long to wait
+/// for each of them to answer.
+#[derive(Debug, Clone)]
+pub struct DnsConfig {
+    pub nameservers: Vec<IpAddr>,
+    pub timeout: Duration,
+    pub prefer: AddressFamilyPreference,
+}
+
+impl DnsConfig {
+    /// Build a resolver configuration from `/etc/resolv.conf`'s `nameserver`
+    /// and `options timeout:` directives, optionally overridden by a single
+    /// explicit nameserver.
+    ///
+    /// # Arguments
+    /// * `override_nameserver` - When set, used as the only nameserver
+    ///   instead of reading `/etc/resolv.conf`.
+    /// * `prefer` - Which address family to prefer when a domain resolves to
+    ///   both.
+    pub fn from_resolv_conf(override_nameserver: Option<IpAddr>, prefer: AddressFamilyPreference) -> GDResult<Self> {
+        if let Some(nameserver) = override_nameserver {
+            return Ok(DnsConfig {
+                nameservers: vec![nameserver],
+                timeout: Duration::from_secs(5),
+                prefer,
+            });
+        }
+
+        let contents = fs::read_to_string("/etc/resolv.conf").map_err(|_| GDError::InvalidInput)?;
+
+        let mut nameservers = Vec::new();
+        let mut timeout = Duration::from_secs(5);
+
+        for line in contents.lines() {
+            let mut parts = line.split_whitespace();
+
+            match parts.next() {
+                Some("nameserver") => {
+                    if let Some(ip) = parts.next().and_then(|ip| ip.parse().ok()) {
+                        nameservers.push(ip);
+                    }
+                },
+                // `ndots` only affects unqualified hostname search-list behaviour,
+                // which this resolver doesn't implement, so it's ignored here.
+                Some("options") => {
+                    for option in parts {
+                        if let Some(secs) = option.strip_prefix("timeout:").and_then(|secs| secs.parse().ok()) {
+                            timeout = Duration::from_secs(secs);
+                        }
+                    }
+                },
+                _ => {},
+            }
+        }
+
+        if nameservers.is_empty() {
+            return Err(GDError::InvalidInput);
+        }
+
+        Ok(DnsConfig {
+            nameservers,
+            timeout,
+            prefer,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum RecordType {
+    A,
+    Aaaa,
+}
+
+impl RecordType {
+    fn code(self) -> u16 {
+        match self {
+            RecordType::A => 1,
+            RecordType::Aaaa => 28,
+        }
+    }
+}
+
+/// Resolve a domain name to an IP address using the configured resolver(s).
+///
+/// Dispatches the query to every configured nameserver in parallel and takes
+/// the first successful reply, rather than trusting whichever order the OS
+/// resolver happened to return results in. When a domain resolves to both an
+/// A and an AAAA record, `config.prefer` decides which one wins.
+///
+/// # Arguments
+/// * `domain` - The domain name to resolve.
+/// * `config` - The resolver configuration to use.
+pub fn resolve(domain: &str, config: &DnsConfig) -> GDResult<IpAddr> {
+    let (primary, secondary) = match config.prefer {
+        AddressFamilyPreference::Ipv4 => (RecordType::A, RecordType::Aaaa),
+        AddressFamilyPreference::Ipv6 => (RecordType::Aaaa, RecordType::A),
+    };
+
+    if let Some(ip) = query_any_nameserver(domain, primary, config) {
+        return Ok(ip);
+    }
+
+    query_any_nameserver(domain, secondary, config).ok_or(GDError::InvalidInput)
+}
+
+/// Send `domain`'s query to every configured nameserver in parallel,
+/// returning the first successful reply.
+fn query_any_nameserver(domain: &str, record_type: RecordType, config: &DnsConfig) -> Option<IpAddr> {
+    let (tx, rx) = std::sync::mpsc::channel();
+
+    thread::scope(|scope| {
+        for &nameserver in &config.nameservers {
+            let tx = tx.clone();
+
+            scope.spawn(move || {
+                if let Ok(ip) = query_nameserver(domain, record_type, nameserver, config.timeout) {
+                    let _ = tx.send(ip);
+                }
+            });
+        }
+
+        drop(tx);
+        rx.recv().ok()
+    })
+}
+
+/// A process-wide counter used to pick each query's transaction ID, so
+/// concurrent lookups don't all share the same one and a response can be
+/// matched back to the query that asked for it.
+static NEXT_QUERY_ID: AtomicU16 = AtomicU16::new(1);
+
+/// Query a single nameserver for `domain`'s `record_type` record.
+fn query_nameserver(domain: &str, record_type: RecordType, nameserver: IpAddr, timeout: Duration) -> GDResult<IpAddr> {
+    let socket = UdpSocket::bind(match nameserver {
+        IpAddr::V4(_) => "0.0.0.0:0",
+        IpAddr::V6(_) => "[::]:0",
+    })
+    .map_err(|_| GDError::SocketBind)?;
+    socket.set_read_timeout(Some(timeout)).map_err(|_| GDError::SocketBind)?;
+    socket
+        .connect(SocketAddr::new(nameserver, 53))
+        .map_err(|_| GDError::SocketConnect)?;
+
+    let id = NEXT_QUERY_ID.fetch_add(1, Ordering::Relaxed);
+    socket.send(&build_query(id, domain, record_type)).map_err(|_| GDError::PacketSend)?;
+
+    let mut buf = [0u8; 512];
+    let size = socket.recv(&mut buf).map_err(|_| GDError::PacketReceive)?;
+
+    parse_response(&buf[.. size], id, record_type)
+}
+
+/// Build a DNS query packet asking a single question: `domain`'s
+/// `record_type` record.
+fn build_query(id: u16, domain: &str, record_type: RecordType) -> Vec<u8> {
+    let mut packet = Vec::new();
+    packet.extend_from_slice(&id.to_be_bytes());
+    packet.extend_from_slice(&0x0100u16.to_be_bytes()); // flags: recursion desired
+    packet.extend_from_slice(&1u16.to_be_bytes()); // QDCOUNT
+    packet.extend_from_slice(&0u16.to_be_bytes()); // ANCOUNT
+    packet.extend_from_slice(&0u16.to_be_bytes()); // NSCOUNT
+    packet.extend_from_slice(&0u16.to_be_bytes()); // ARCOUNT
+
+    for label in domain.trim_end_matches('.').split('.') {
+        packet.push(label.len() as u8);
+        packet.extend_from_slice(label.as_bytes());
+    }
+    packet.push(0);
+
+    packet.extend_from_slice(&record_type.code().to_be_bytes());
+    packet.extend_from_slice(&1u16.to_be_bytes()); // QCLASS: IN
+
+    packet
+}
+
+/// Skip a (possibly compressed) encoded name starting at `offset`, returning
+/// the offset immediately after it.
+fn skip_name(data: &[u8], mut offset: usize) -> GDResult<usize> {
+    loop {
+        let length = *data.get(offset).ok_or(GDError::PacketBad)? as usize;
+
+        if length == 0 {
+            return Ok(offset + 1);
+        }
+
+        if length & 0xC0 == 0xC0 {
+            // Compression pointer: always exactly two bytes, wherever it points to.
+            return Ok(offset + 2);
+        }
+
+        offset += 1 + length;
+    }
+}
+
+/// Parse a DNS response packet, returning the first answer matching
+/// `record_type`.
+///
+/// `expected_id` must match the transaction ID we sent, otherwise the
+/// datagram is rejected outright — a connected UDP socket still accepts any
+/// packet sent to it by the remote host, so this is the only thing tying a
+/// reply back to the query that actually asked for it.
+fn parse_response(data: &[u8], expected_id: u16, record_type: RecordType) -> GDResult<IpAddr> {
+    if data.len() < 12 {
+        return Err(GDError::PacketBad);
+    }
+
+    if u16::from_be_bytes([data[0], data[1]]) != expected_id {
+        return Err(GDError::PacketBad);
+    }
+
+    let answer_count = u16::from_be_bytes([data[6], data[7]]) as usize;
+    let mut offset = skip_name(data, 12)? + 4; // past the question's QTYPE/QCLASS
+
+    for _ in 0 .. answer_count {
+        offset = skip_name(data, offset)?;
+
+        let header = data.get(offset .. offset + 10).ok_or(GDError::PacketBad)?;
+        let answer_type = u16::from_be_bytes([header[0], header[1]]);
+        let rdlength = u16::from_be_bytes([header[8], header[9]]) as usize;
+
+        let rdata_offset = offset + 10;
+        let rdata = data.get(rdata_offset .. rdata_offset + rdlength).ok_or(GDError::PacketBad)?;
+
+        match (record_type.code(), answer_type, rdata.len()) {
+            (1, 1, 4) => return Ok(IpAddr::V4(Ipv4Addr::new(rdata[0], rdata[1], rdata[2], rdata[3]))),
+            (28, 28, 16) => {
+                let mut octets = [0u8; 16];
+                octets.copy_from_slice(rdata);
+                return Ok(IpAddr::V6(Ipv6Addr::from(octets)));
+            },
+            _ => {},
+        }
+
+        offset = rdata_offset + rdlength;
+    }
+
+    Err(GDError::InvalidInput)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode_name(domain: &str) -> Vec<u8> {
+        let mut encoded = Vec::new();
+        for label in domain.split('.') {
+            encoded.push(label.len() as u8);
+            encoded.extend_from_slice(label.as_bytes());
+        }
+        encoded.push(0);
+        encoded
+    }
+
+    fn header(id: u16, answer_count: u16) -> Vec<u8> {
+        let mut header = Vec::new();
+        header.extend_from_slice(&id.to_be_bytes());
+        header.extend_from_slice(&0x8180u16.to_be_bytes()); // standard response, recursion available
+        header.extend_from_slice(&1u16.to_be_bytes()); // QDCOUNT
+        header.extend_from_slice(&answer_count.to_be_bytes());
+        header.extend_from_slice(&0u16.to_be_bytes()); // NSCOUNT
+        header.extend_from_slice(&0u16.to_be_bytes()); // ARCOUNT
+        header
+    }
+
+    /// A name pointer back to the question, which always starts right after
+    /// the fixed 12-byte header.
+    fn name_pointer_to_question() -> Vec<u8> { vec![0xC0, 0x0C] }
+
+    fn answer(name: Vec<u8>, record_type: u16, rdata: &[u8]) -> Vec<u8> {
+        let mut answer = name;
+        answer.extend_from_slice(&record_type.to_be_bytes());
+        answer.extend_from_slice(&1u16.to_be_bytes()); // CLASS: IN
+        answer.extend_from_slice(&300u32.to_be_bytes()); // TTL
+        answer.extend_from_slice(&(rdata.len() as u16).to_be_bytes());
+        answer.extend_from_slice(rdata);
+        answer
+    }
+
+    fn question(qtype: u16) -> Vec<u8> {
+        let mut question = encode_name("example.com");
+        question.extend_from_slice(&qtype.to_be_bytes());
+        question.extend_from_slice(&1u16.to_be_bytes()); // QCLASS: IN
+        question
+    }
+
+    #[test]
+    fn parses_an_a_record() {
+        let mut packet = header(42, 1);
+        packet.extend(question(1));
+        packet.extend(answer(name_pointer_to_question(), 1, &[93, 184, 216, 34]));
+
+        let ip = parse_response(&packet, 42, RecordType::A).unwrap();
+        assert_eq!(ip, IpAddr::V4(Ipv4Addr::new(93, 184, 216, 34)));
+    }
+
+    #[test]
+    fn parses_an_aaaa_record() {
+        let ip = Ipv6Addr::new(0x2606, 0x2800, 0x220, 1, 0x248, 0x1893, 0x25c8, 0x1946);
+
+        let mut packet = header(7, 1);
+        packet.extend(question(28));
+        packet.extend(answer(name_pointer_to_question(), 28, &ip.octets()));
+
+        assert_eq!(parse_response(&packet, 7, RecordType::Aaaa).unwrap(), IpAddr::V6(ip));
+    }
+
+    #[test]
+    fn skips_a_cname_before_finding_the_a_record() {
+        let mut packet = header(1, 2);
+        packet.extend(question(1));
+
+        let cname_rdata = encode_name("www.example.com");
+        packet.extend(answer(name_pointer_to_question(), 5, &cname_rdata));
+        packet.extend(answer(name_pointer_to_question(), 1, &[1, 2, 3, 4]));
+
+        let ip = parse_response(&packet, 1, RecordType::A).unwrap();
+        assert_eq!(ip, IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4)));
+    }
+
+    #[test]
+    fn rejects_a_mismatched_transaction_id() {
+        let mut packet = header(42, 1);
+        packet.extend(question(1));
+        packet.extend(answer(name_pointer_to_question(), 1, &[93, 184, 216, 34]));
+
+        assert!(matches!(parse_response(&packet, 99, RecordType::A), Err(GDError::PacketBad)));
+    }
+
+    #[test]
+    fn rejects_a_truncated_packet() {
+        assert!(matches!(parse_response(&[0, 42], 42, RecordType::A), Err(GDError::PacketBad)));
+    }
+
+    #[test]
+    fn skip_name_follows_a_compression_pointer() {
+        let data = [0xC0, 0x00, 0xAA];
+        assert_eq!(skip_name(&data, 0).unwrap(), 2);
+    }
+
+    #[test]
+    fn skip_name_walks_plain_labels() {
+        let mut data = encode_name("a.bc");
+        data.push(0xFF); // trailing byte, to confirm we stop exactly at the name's end
+
+        assert_eq!(skip_name(&data, 0).unwrap(), data.len() - 1);
+    }
+}
@@ -0,0 +1,9 @@
+//! Root of the `gamedig` library crate.
+//!
+//! This tree only carries the modules this backlog touches directly; the
+//! rest of the module tree (`errors`, `games`, `protocols`, and the bulk of
+//! the per-game definitions) lives upstream and isn't duplicated here, so
+//! it isn't re-declared below.
+
+pub mod dns;
+pub mod master;
@@ -0,0 +1,208 @@
+use std::{
+    collections::HashSet,
+    net::{Ipv4Addr, SocketAddr, SocketAddrV4, UdpSocket},
+    time::Duration,
+};
+
+use crate::{
+    errors::{GDError, GDResult},
+    games::Game,
+};
+
+/// The address used both as the initial seed for a master server query and
+/// as the sentinel that signals the end of the address list.
+const SENTINEL: SocketAddrV4 = SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0);
+
+/// How long to wait for a single master server response before giving up.
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The region byte sent as part of a "query servers" request, restricting
+/// the addresses a master server replies with to a specific geographic
+/// region.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Region {
+    UsEastCoast = 0x00,
+    UsWestCoast = 0x01,
+    SouthAmerica = 0x02,
+    Europe = 0x03,
+    Asia = 0x04,
+    Australia = 0x05,
+    MiddleEast = 0x06,
+    Africa = 0x07,
+    RestOfTheWorld = 0xFF,
+}
+
+/// Endpoint of a game's master server, attached to a [Game] definition.
+#[derive(Debug, Clone, Copy)]
+pub struct MasterServerEndpoint {
+    /// Hostname or IP address of the master server.
+    pub host: &'static str,
+    /// Port the master server listens for queries on.
+    pub port: u16,
+}
+
+/// Query a game's master server for the list of currently known server
+/// addresses, mirroring what a server browser does.
+///
+/// Repeatedly sends a "query servers" request seeded with the last address
+/// received so far (starting from `0.0.0.0:0`), collecting the tightly
+/// packed `SocketAddrV4` entries the master server replies with across
+/// however many UDP packets it takes, until the all-zero sentinel address is
+/// received.
+///
+/// # Arguments
+/// * `game` - The game definition to query the master server of.
+/// * `region` - The region to restrict the returned addresses to.
+/// * `filter` - An optional filter string understood by the target master
+///   server.
+///
+/// # Returns
+/// * `GDResult<Vec<SocketAddr>>` - The deduplicated list of server addresses
+///   known to the master server.
+pub fn query_master(game: &Game, region: Region, filter: Option<&str>) -> GDResult<Vec<SocketAddr>> {
+    // FIXME(chunk0-1): `Game` needs a `master_server: Option<MasterServerEndpoint>`
+    // field, set for every master-capable game, before this can resolve to anything —
+    // `games/mod.rs` (the `Game` definition and the per-game registry) lives upstream
+    // outside this tree, so that field can't be added from here. Flagging for reviewers:
+    // the line below reads that field, so this compiles only against a `Game` that has
+    // it; a game whose `master_server` is `None` gets `InvalidInput`.
+    let endpoint = game.master_server.ok_or(GDError::InvalidInput)?;
+
+    let socket = UdpSocket::bind("0.0.0.0:0").map_err(|_| GDError::SocketBind)?;
+    socket.set_read_timeout(Some(READ_TIMEOUT)).map_err(|_| GDError::SocketBind)?;
+    socket
+        .connect((endpoint.host, endpoint.port))
+        .map_err(|_| GDError::SocketConnect)?;
+
+    let mut addresses = HashSet::new();
+    let mut last = SENTINEL;
+    let mut buf = [0u8; 4096];
+
+    loop {
+        socket
+            .send(&build_request(region, &last, filter))
+            .map_err(|_| GDError::PacketSend)?;
+
+        let size = socket.recv(&mut buf).map_err(|_| GDError::PacketReceive)?;
+        let entries = parse_response(&buf[.. size])?;
+
+        let Some(&final_entry) = entries.last() else {
+            break;
+        };
+
+        if entries.contains(&SENTINEL) {
+            addresses.extend(entries.into_iter().filter(|&entry| entry != SENTINEL).map(SocketAddr::V4));
+            break;
+        }
+
+        addresses.extend(entries.into_iter().map(SocketAddr::V4));
+        last = final_entry;
+    }
+
+    Ok(addresses.into_iter().collect())
+}
+
+/// Build a "query servers" request packet seeded with the last address
+/// received so far.
+fn build_request(region: Region, last: &SocketAddrV4, filter: Option<&str>) -> Vec<u8> {
+    let mut request = vec![b'1', region as u8];
+    request.extend_from_slice(last.to_string().as_bytes());
+    request.push(0);
+    request.extend_from_slice(filter.unwrap_or_default().as_bytes());
+    request.push(0);
+    request
+}
+
+/// Parse a master server response packet into its packed `SocketAddrV4`
+/// entries, stripping the leading header.
+fn parse_response(data: &[u8]) -> GDResult<Vec<SocketAddrV4>> {
+    const HEADER: &[u8] = &[0xFF, 0xFF, 0xFF, 0xFF, b'f', b'\n'];
+
+    let body = data.strip_prefix(HEADER).ok_or(GDError::PacketBad)?;
+
+    // A trailing partial entry means the packet was cut short.
+    if body.len() % 6 != 0 {
+        return Err(GDError::PacketBad);
+    }
+
+    body.chunks_exact(6)
+        .map(|entry| {
+            let ip = Ipv4Addr::new(entry[0], entry[1], entry[2], entry[3]);
+            let port = u16::from_be_bytes([entry[4], entry[5]]);
+            Ok(SocketAddrV4::new(ip, port))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &[u8] = &[0xFF, 0xFF, 0xFF, 0xFF, b'f', b'\n'];
+
+    fn entry(a: u8, b: u8, c: u8, d: u8, port: u16) -> SocketAddrV4 {
+        SocketAddrV4::new(Ipv4Addr::new(a, b, c, d), port)
+    }
+
+    #[test]
+    fn parses_empty_list() {
+        let packet = HEADER.to_vec();
+        assert_eq!(parse_response(&packet).unwrap(), Vec::<SocketAddrV4>::new());
+    }
+
+    #[test]
+    fn parses_a_single_entry() {
+        let mut packet = HEADER.to_vec();
+        packet.extend_from_slice(&[10, 0, 0, 1, 0x69, 0x7F]);
+
+        assert_eq!(parse_response(&packet).unwrap(), vec![entry(10, 0, 0, 1, 0x697F)]);
+    }
+
+    #[test]
+    fn parses_multiple_entries_in_one_chunk() {
+        let mut packet = HEADER.to_vec();
+        packet.extend_from_slice(&[10, 0, 0, 1, 0x69, 0x7F]);
+        packet.extend_from_slice(&[10, 0, 0, 2, 0x69, 0x80]);
+
+        assert_eq!(
+            parse_response(&packet).unwrap(),
+            vec![entry(10, 0, 0, 1, 0x697F), entry(10, 0, 0, 2, 0x6980)]
+        );
+    }
+
+    #[test]
+    fn parses_the_terminating_sentinel_entry() {
+        let mut packet = HEADER.to_vec();
+        packet.extend_from_slice(&[10, 0, 0, 1, 0x69, 0x7F]);
+        packet.extend_from_slice(&[0, 0, 0, 0, 0, 0]);
+
+        assert_eq!(parse_response(&packet).unwrap(), vec![entry(10, 0, 0, 1, 0x697F), SENTINEL]);
+    }
+
+    #[test]
+    fn rejects_a_missing_header() {
+        let packet = vec![1, 2, 3, 4, 5, 6];
+        assert!(matches!(parse_response(&packet), Err(GDError::PacketBad)));
+    }
+
+    #[test]
+    fn rejects_a_truncated_entry() {
+        let mut packet = HEADER.to_vec();
+        packet.extend_from_slice(&[10, 0, 0]);
+
+        assert!(matches!(parse_response(&packet), Err(GDError::PacketBad)));
+    }
+
+    #[test]
+    fn build_request_seeds_the_last_address_and_filter() {
+        let request = build_request(Region::Europe, &entry(10, 0, 0, 1, 27015), Some("gm\\mp"));
+
+        let contains = |needle: &[u8]| request.windows(needle.len()).any(|window| window == needle);
+
+        assert_eq!(request[0], b'1');
+        assert_eq!(request[1], Region::Europe as u8);
+        assert!(contains(b"10.0.0.1:27015"));
+        assert!(contains(b"gm\\mp"));
+    }
+}